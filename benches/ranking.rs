@@ -0,0 +1,111 @@
+//! Benchmarks the full ranking phase (tight tree + network simplex) on
+//! random DAGs of increasing size, to demonstrate the win from the dense
+//! `Vec`-backed `Ranks` storage over the previous `HashMap`-backed one.
+//!
+//! Run with `cargo bench --bench ranking`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use petgraph::stable_graph::StableDiGraph;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use rust_sugiyama::graphs::p1_layering::UnlayeredGraph;
+
+/// A thin wrapper around the system allocator that tracks current and peak
+/// bytes allocated, so the benchmark can report memory use alongside wall
+/// time instead of only the latter.
+struct TrackingAllocator;
+
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT_BYTES.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK_BYTES.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+fn reset_peak_bytes() {
+    PEAK_BYTES.store(CURRENT_BYTES.load(Ordering::SeqCst), Ordering::SeqCst);
+}
+
+fn peak_bytes() -> usize {
+    PEAK_BYTES.load(Ordering::SeqCst)
+}
+
+/// Builds a random connected DAG with `node_count` vertices: every vertex
+/// after the first gets an edge from a random lower-indexed vertex, since
+/// the ranking phase expects a connected graph, and then an edge from a
+/// lower-indexed vertex to a higher-indexed one is added with probability
+/// `density` for every ordered pair; ordering indices this way guarantees
+/// the result is acyclic.
+fn random_dag(node_count: usize, density: f64, seed: u64) -> StableDiGraph<Option<isize>, usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut graph = StableDiGraph::new();
+    let nodes: Vec<_> = (0..node_count).map(|_| graph.add_node(None)).collect();
+
+    for j in 1..node_count {
+        graph.add_edge(nodes[rng.gen_range(0..j)], nodes[j], 1);
+    }
+
+    for i in 0..node_count {
+        for j in (i + 1)..node_count {
+            if rng.gen_bool(density) {
+                graph.add_edge(nodes[i], nodes[j], 1);
+            }
+        }
+    }
+
+    graph
+}
+
+fn ranking_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ranking_phase");
+    group.measurement_time(Duration::from_secs(10));
+
+    for &node_count in &[100usize, 500, 1_000, 5_000] {
+        let graph = random_dag(node_count, 0.01, 42);
+
+        // One untimed run outside Criterion's measurement loop to report
+        // peak allocation for this graph size; Criterion itself only times.
+        reset_peak_bytes();
+        let ul_graph = UnlayeredGraph::new(graph.clone());
+        ul_graph.initial_ranking(1);
+        println!(
+            "ranking_phase/{node_count}: peak allocation {} KiB",
+            peak_bytes() / 1024
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(node_count),
+            &graph,
+            |b, graph| {
+                b.iter(|| {
+                    let ul_graph = UnlayeredGraph::new(graph.clone());
+                    ul_graph.initial_ranking(1)
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, ranking_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableDiGraph};
+use petgraph::Direction;
+
+/// The result of [`break_cycles`]: which edges were reversed to make the
+/// graph acyclic, so the layout phase can flip them back once the drawing is
+/// built.
+#[derive(Debug, Clone, Default)]
+pub(super) struct FeedbackArcSet {
+    reversed: HashSet<EdgeIndex>,
+}
+
+impl FeedbackArcSet {
+    /// Flips every edge this set reversed back to its original orientation.
+    /// Call once ranking (and any later phase relying on the acyclic graph)
+    /// is done.
+    pub(super) fn restore<T>(self, graph: &mut StableDiGraph<Option<T>, usize>) {
+        for edge in self.reversed {
+            let (tail, head) = graph.edge_endpoints(edge).unwrap();
+            let weight = graph.remove_edge(edge).unwrap();
+            graph.add_edge(head, tail, weight);
+        }
+    }
+}
+
+/// Makes `graph` acyclic in place by reversing a greedy feedback arc set
+/// (Eades–Lin–Smyth heuristic), and returns the set of edges that were
+/// reversed so callers can restore the original orientation after layout.
+///
+/// The heuristic repeatedly peels vertices off the graph: all current sinks
+/// are removed and prepended to a right-hand sequence, then all current
+/// sources are removed and appended to a left-hand sequence, then among the
+/// vertices left the one maximizing `out_degree - in_degree` is removed and
+/// appended to the left-hand sequence. Concatenating left and right gives a
+/// total order in which any edge pointing from a later vertex to an earlier
+/// one is a "backward" edge; those are the ones reversed.
+pub(super) fn break_cycles<T>(graph: &mut StableDiGraph<Option<T>, usize>) -> FeedbackArcSet {
+    let order = eades_lin_smyth_order(graph);
+    let position: HashMap<NodeIndex, usize> = order
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (v, i))
+        .collect();
+
+    let mut reversed = HashSet::new();
+    for edge in graph.edge_indices().collect::<Vec<_>>() {
+        let (tail, head) = graph.edge_endpoints(edge).unwrap();
+        if position[&tail] > position[&head] {
+            let weight = graph.remove_edge(edge).unwrap();
+            // Remember the index `add_edge` returns for the reversed edge
+            // rather than `edge`, which was removed; the stable graph may
+            // hand back that freed slot or a different one.
+            let new_edge = graph.add_edge(head, tail, weight);
+            reversed.insert(new_edge);
+        }
+    }
+
+    FeedbackArcSet { reversed }
+}
+
+fn eades_lin_smyth_order<T>(graph: &StableDiGraph<Option<T>, usize>) -> Vec<NodeIndex> {
+    let mut remaining: StableDiGraph<(), ()> = StableDiGraph::new();
+    let mut mapping = HashMap::new();
+    for v in graph.node_indices() {
+        mapping.insert(v, remaining.add_node(()));
+    }
+    let reverse_mapping: HashMap<_, _> = mapping.iter().map(|(&k, &v)| (v, k)).collect();
+    for e in graph.edge_indices() {
+        let (tail, head) = graph.edge_endpoints(e).unwrap();
+        remaining.add_edge(mapping[&tail], mapping[&head], ());
+    }
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+
+    while remaining.node_count() > 0 {
+        let mut progressed = true;
+        while progressed {
+            progressed = false;
+            let sinks: Vec<_> = remaining
+                .node_indices()
+                .filter(|v| remaining.neighbors_directed(*v, Direction::Outgoing).count() == 0)
+                .collect();
+            for v in sinks {
+                right.insert(0, v);
+                remaining.remove_node(v);
+                progressed = true;
+            }
+
+            let sources: Vec<_> = remaining
+                .node_indices()
+                .filter(|v| remaining.neighbors_directed(*v, Direction::Incoming).count() == 0)
+                .collect();
+            for v in sources {
+                left.push(v);
+                remaining.remove_node(v);
+                progressed = true;
+            }
+        }
+
+        if remaining.node_count() > 0 {
+            let v = remaining
+                .node_indices()
+                .max_by_key(|v| {
+                    let out = remaining.neighbors_directed(*v, Direction::Outgoing).count() as isize;
+                    let r#in = remaining.neighbors_directed(*v, Direction::Incoming).count() as isize;
+                    out - r#in
+                })
+                .unwrap();
+            left.push(v);
+            remaining.remove_node(v);
+        }
+    }
+
+    left.extend(right);
+    left.into_iter().map(|v| reverse_mapping[&v]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::algo::is_cyclic_directed;
+    use petgraph::stable_graph::StableDiGraph;
+
+    use super::break_cycles;
+
+    #[test]
+    fn test_break_cycles_makes_graph_acyclic_and_is_reversible() {
+        let mut graph: StableDiGraph<Option<()>, usize> = StableDiGraph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(None)).collect();
+        graph.add_edge(n[0], n[1], 1);
+        graph.add_edge(n[1], n[2], 1);
+        graph.add_edge(n[2], n[0], 1);
+        graph.add_edge(n[2], n[3], 1);
+
+        assert!(is_cyclic_directed(&graph));
+
+        let original_edges: Vec<_> = graph
+            .edge_indices()
+            .map(|e| graph.edge_endpoints(e).unwrap())
+            .collect();
+
+        let reversed = break_cycles(&mut graph);
+        assert!(!is_cyclic_directed(&graph));
+
+        reversed.restore(&mut graph);
+        let mut restored_edges: Vec<_> = graph
+            .edge_indices()
+            .map(|e| graph.edge_endpoints(e).unwrap())
+            .collect();
+        let mut original_edges_sorted = original_edges;
+        restored_edges.sort();
+        original_edges_sorted.sort();
+        assert_eq!(restored_edges, original_edges_sorted);
+    }
+}
@@ -4,40 +4,190 @@ use petgraph::stable_graph::{NodeIndex, StableDiGraph};
 
 use crate::util::layers::Layers;
 
-use super::tree::{TightTreeDFSs};
+use super::coffman_graham;
+use super::tree::TightTreeDFSs;
+
+/// Selects how [`Ranks`] are produced from a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingStrategy {
+    /// The default longest-path-style tight tree, optimized to minimum
+    /// weighted edge length via network simplex.
+    NetworkSimplex,
+    /// Coffman–Graham layering, bounding every layer to at most `width`
+    /// vertices.
+    CoffmanGraham { width: usize },
+}
+
+/// Backing storage for [`Ranks`]. `petgraph`'s `NodeIndex` is a contiguous
+/// `usize` for graphs that have never had a node removed, so in the common
+/// case ranks live in a plain `Vec` indexed directly by `NodeIndex::index()`
+/// and `slack`/`normalize`/`into_layers` are array operations rather than
+/// hash lookups. Once a graph has had nodes removed, indices may be sparse
+/// enough that a dense `Vec` would waste memory, so we fall back to a
+/// `HashMap` keyed by `NodeIndex` in that case.
+#[derive(Debug, Clone)]
+enum Storage {
+    Dense(Vec<Option<isize>>),
+    Sparse(HashMap<NodeIndex, isize>),
+}
+
+/// A graph is considered sparse, and thus worth compacting into a `HashMap`,
+/// once fewer than half of the index range `0..node_bound` is actually in
+/// use.
+const DENSE_LOAD_FACTOR: f64 = 0.5;
+
+impl Storage {
+    fn from_map(ranks: HashMap<NodeIndex, isize>, node_bound: usize) -> Self {
+        if ranks.is_empty() {
+            return Storage::Dense(Vec::new());
+        }
+
+        let is_dense = ranks.len() as f64 >= node_bound as f64 * DENSE_LOAD_FACTOR;
+        if is_dense {
+            let mut dense = vec![None; node_bound];
+            for (v, rank) in ranks {
+                dense[v.index()] = Some(rank);
+            }
+            Storage::Dense(dense)
+        } else {
+            Storage::Sparse(ranks)
+        }
+    }
+
+    fn get(&self, vertex: NodeIndex) -> isize {
+        match self {
+            Storage::Dense(ranks) => ranks[vertex.index()].unwrap(),
+            Storage::Sparse(ranks) => *ranks.get(&vertex).unwrap(),
+        }
+    }
+
+    fn update(&mut self, vertex: NodeIndex, delta: isize) {
+        match self {
+            Storage::Dense(ranks) => {
+                if let Some(rank) = ranks[vertex.index()].as_mut() {
+                    *rank += delta;
+                }
+            }
+            Storage::Sparse(ranks) => {
+                ranks.entry(vertex).and_modify(|rank| *rank += delta);
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (NodeIndex, isize)> + '_> {
+        match self {
+            Storage::Dense(ranks) => Box::new(
+                ranks
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, r)| r.map(|r| (NodeIndex::new(i), r))),
+            ),
+            Storage::Sparse(ranks) => Box::new(ranks.iter().map(|(v, r)| (*v, *r))),
+        }
+    }
+
+    fn iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut isize> + '_> {
+        match self {
+            Storage::Dense(ranks) => Box::new(ranks.iter_mut().filter_map(|r| r.as_mut())),
+            Storage::Sparse(ranks) => Box::new(ranks.values_mut()),
+        }
+    }
+}
 
 /// Ranks of the vertices of a graph.
 /// Needs to contain all the vertices of a graph
 #[derive(Debug, Clone)]
 pub struct Ranks {
-_inner: HashMap<NodeIndex, isize>,
-minimum_length: usize
+_inner: Storage,
+minimum_length: usize,
+// per-edge minimum rank separation (delta), overriding `minimum_length`
+// where present; absent entries fall back to `minimum_length`.
+edge_minimum_lengths: HashMap<(NodeIndex, NodeIndex), usize>,
+// per-edge weight (omega), overriding the graph's own `usize` edge
+// payload where present; absent entries fall back to that payload.
+edge_weights: HashMap<(NodeIndex, NodeIndex), usize>
 }
 
 impl Ranks {
     pub fn new<T>(ranks: HashMap<NodeIndex, isize>, graph: &StableDiGraph<Option<T>, usize>, minimum_length: usize) -> Self {
         assert!(Self::is_valid(&ranks, graph));
-        Ranks { _inner: ranks, minimum_length }
+        let node_bound = graph.node_indices().map(|v| v.index() + 1).max().unwrap_or(0);
+        Ranks {
+            _inner: Storage::from_map(ranks, node_bound),
+            minimum_length,
+            edge_minimum_lengths: HashMap::new(),
+            edge_weights: HashMap::new(),
+        }
+    }
+
+    /// Overrides the minimum rank separation (delta) for specific edges,
+    /// e.g. to require an edge to span at least two ranks. Edges not present
+    /// in `lengths` keep using the graph-wide `minimum_length`.
+    pub fn with_edge_minimum_lengths(
+        mut self,
+        lengths: HashMap<(NodeIndex, NodeIndex), usize>,
+    ) -> Self {
+        self.edge_minimum_lengths = lengths;
+        self
+    }
+
+    /// Overrides the weight (omega) used when scoring an edge's contribution
+    /// to total ranked length during network simplex, e.g. to draw a
+    /// "heavy" edge short. Edges not present in `weights` keep using the
+    /// graph's own edge payload.
+    pub fn with_edge_weights(mut self, weights: HashMap<(NodeIndex, NodeIndex), usize>) -> Self {
+        self.edge_weights = weights;
+        self
+    }
+
+    fn edge_weight(&self, tail: NodeIndex, head: NodeIndex, graph_weight: usize) -> usize {
+        self.edge_weights
+            .get(&(tail, head))
+            .copied()
+            .unwrap_or(graph_weight)
+    }
+
+    /// Builds ranks via Coffman–Graham layering, bounding every layer to at
+    /// most `width` vertices. Used in place of the tight-tree/network-simplex
+    /// pipeline when the caller selects [`RankingStrategy::CoffmanGraham`].
+    pub fn coffman_graham<T>(
+        graph: &StableDiGraph<Option<T>, usize>,
+        minimum_length: usize,
+        width: usize,
+    ) -> Self {
+        let ranks = coffman_graham::rank(graph, width);
+        Ranks::new(ranks, graph, minimum_length)
     }
 
     #[cfg(test)]
     pub fn new_unchecked(ranks: HashMap<NodeIndex, isize>, minimum_length: usize) -> Self {
-        Ranks { _inner: ranks, minimum_length }
+        let node_bound = ranks.keys().map(|v| v.index() + 1).max().unwrap_or(0);
+        Ranks {
+            _inner: Storage::from_map(ranks, node_bound),
+            minimum_length,
+            edge_minimum_lengths: HashMap::new(),
+            edge_weights: HashMap::new(),
+        }
     }
-    
+
     fn is_valid<T>(ranks: &HashMap<NodeIndex, isize>, graph: &StableDiGraph<Option<T>, usize>) -> bool {
         for v in graph.node_indices() {
             if !ranks.contains_key(&v) {
                 return false;
             }
-        } 
+        }
 
         true
     }
 
     // tail = predecessor, head = successor
     pub(super) fn slack(&self, tail: NodeIndex, head: NodeIndex) -> isize {
-        self._inner.get(&head).unwrap() - self._inner.get(&tail).unwrap() - self.minimum_length as isize
+        let delta = self
+            .edge_minimum_lengths
+            .get(&(tail, head))
+            .copied()
+            .unwrap_or(self.minimum_length);
+        self._inner.get(head) - self._inner.get(tail) - delta as isize
     }
 
     pub(super) fn get_minimum_length(&self) -> usize {
@@ -45,7 +195,7 @@ impl Ranks {
     }
 
     pub(super) fn update(&mut self, vertex: NodeIndex, delta: isize) {
-        self._inner.entry(vertex).and_modify(|rank| *rank += delta);
+        self._inner.update(vertex, delta);
     }
 
     pub(super) fn tighten_edge(&mut self, tree: &TightTreeDFSs, delta: isize) {
@@ -54,10 +204,92 @@ impl Ranks {
         }
     }
 
+    /// Iterates the feasible tight tree produced by [`TightTreeDFSs`] to an
+    /// optimal ranking, following the network simplex method of Gansner et
+    /// al.: while some tree edge has a negative cut value, it is exchanged
+    /// for the minimum-slack non-tree edge that reconnects the two
+    /// components in the opposite direction, and the ranks of one component
+    /// are shifted so the new tree edge is tight. Terminates once every tree
+    /// edge has a non-negative cut value, then normalizes.
+    pub(super) fn network_simplex<T>(
+        &mut self,
+        tree: &mut TightTreeDFSs,
+        graph: &StableDiGraph<Option<T>, usize>,
+    ) {
+        loop {
+            let leave_edge = tree
+                .edges()
+                .copied()
+                .find(|(tail, head)| self.cut_value(tree, graph, *tail, *head) < 0);
+
+            let Some((leave_tail, leave_head)) = leave_edge else {
+                break;
+            };
+
+            let (tail_component, _head_component) = tree.split_on_edge(leave_tail, leave_head);
+
+            // Find the non-tree edge of minimum slack that re-enters the
+            // tail component from outside it, in the opposite direction of
+            // the edge being removed.
+            let enter_edge = graph
+                .edge_indices()
+                .filter_map(|e| graph.edge_endpoints(e))
+                .filter(|(tail, head)| tail_component.contains(head) && !tail_component.contains(tail))
+                .min_by_key(|(tail, head)| self.slack(*tail, *head));
+
+            let Some((enter_tail, enter_head)) = enter_edge else {
+                // No edge can reconnect the components: the cut is already
+                // optimal given the graph's connectivity.
+                break;
+            };
+
+            let delta = self.slack(enter_tail, enter_head);
+            tree.remove_edge(leave_tail, leave_head);
+            tree.insert_edge(enter_tail, enter_head);
+
+            // `delta` is the (non-negative) slack of the entering edge. The
+            // leave edge's tail component sits on the "low rank" side of the
+            // cut, so it must move *down* by `delta`, not up, for the
+            // entering edge to become tight.
+            for v in &tail_component {
+                self.update(*v, -delta);
+            }
+        }
+
+        self.normalize();
+    }
+
+    /// The cut value of a tree edge is the sum of the weights of all graph
+    /// edges crossing the cut (induced by removing the tree edge) in the
+    /// same direction as the tree edge, minus the sum of those crossing in
+    /// the opposite direction.
+    fn cut_value<T>(
+        &self,
+        tree: &TightTreeDFSs,
+        graph: &StableDiGraph<Option<T>, usize>,
+        tail: NodeIndex,
+        head: NodeIndex,
+    ) -> isize {
+        let (tail_component, _) = tree.split_on_edge(tail, head);
+
+        graph
+            .edge_indices()
+            .filter_map(|e| graph.edge_endpoints(e).map(|(t, h)| (t, h, self.edge_weight(t, h, graph[e]))))
+            .filter(|(t, h, _)| tail_component.contains(t) != tail_component.contains(h))
+            .map(|(t, _h, weight)| {
+                if tail_component.contains(&t) {
+                    weight as isize
+                } else {
+                    -(weight as isize)
+                }
+            })
+            .sum()
+    }
+
     /// Normalize ranking so the least rank is 0
     pub fn normalize(&mut self) {
-        let minimum_rank = *self._inner.iter().min_by(|(_, rank_a), (_, rank_b)| rank_a.cmp(&rank_b)).unwrap().1;
-        for (_, rank) in self._inner.iter_mut() {
+        let minimum_rank = self._inner.iter().map(|(_, r)| r).min().unwrap();
+        for rank in self._inner.iter_mut() {
             *rank -= minimum_rank;
         }
     }
@@ -65,13 +297,10 @@ impl Ranks {
     /// Note: Ranks have to be normalized, or this will fail.
     pub(super) fn into_layers<T>(mut self, graph: &StableDiGraph<Option<T>, usize>) -> Layers {
         self.normalize();
-        let mut layers = vec![];
-        for (vertex, layer) in self._inner {
-            while layers.len() <= layer as usize {
-                layers.push(vec![]);
-            }
+        let max_layer = self._inner.iter().map(|(_, r)| r).max().unwrap_or(0);
+        let mut layers = vec![vec![]; max_layer as usize + 1];
+        for (vertex, layer) in self._inner.iter() {
             layers[layer as usize].push(vertex);
-        
         }
         Layers::new(layers, graph)
     }
@@ -81,7 +310,10 @@ impl Index<NodeIndex> for Ranks {
     type Output = isize;
 
     fn index(&self, index: NodeIndex) -> &Self::Output {
-        self._inner.get(&index).unwrap()
+        match &self._inner {
+            Storage::Dense(ranks) => ranks[index.index()].as_ref().unwrap(),
+            Storage::Sparse(ranks) => ranks.get(&index).unwrap(),
+        }
     }
 }
 
@@ -89,9 +321,95 @@ impl Index<NodeIndex> for Ranks {
 pub mod tests {
     use std::collections::HashMap;
 
+    use petgraph::stable_graph::StableDiGraph;
+
     use crate::graphs::p1_layering::tests::create_test_graph;
 
-    use super::{Ranks, super::UnlayeredGraph};
+    use super::{Ranks, TightTreeDFSs, super::UnlayeredGraph};
+
+    #[test]
+    fn test_network_simplex_optimizes_heavy_edge() {
+        // 0->1->4 (heavy, weight 10) in parallel with the longer, lighter
+        // chain 0->2->3->4. The heavy edge starts at length 2 and should be
+        // shortened to the minimum length 1, with the light edge 0->1
+        // stretching to length 2 instead.
+        let mut graph: StableDiGraph<Option<()>, usize> = StableDiGraph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(None)).collect();
+        graph.add_edge(n[0], n[1], 1);
+        graph.add_edge(n[1], n[4], 10);
+        graph.add_edge(n[0], n[2], 1);
+        graph.add_edge(n[2], n[3], 1);
+        graph.add_edge(n[3], n[4], 1);
+
+        let ranks_raw = HashMap::from([
+            (n[0], 0),
+            (n[1], 1),
+            (n[2], 1),
+            (n[3], 2),
+            (n[4], 3),
+        ]);
+        let mut ranks = Ranks::new(ranks_raw, &graph, 1);
+
+        let mut tree = TightTreeDFSs::new();
+        tree.insert_edge(n[0], n[1]);
+        tree.insert_edge(n[0], n[2]);
+        tree.insert_edge(n[2], n[3]);
+        tree.insert_edge(n[3], n[4]);
+
+        ranks.network_simplex(&mut tree, &graph);
+
+        assert_eq!(ranks[n[0]], 0);
+        assert_eq!(ranks[n[1]], 2);
+        assert_eq!(ranks[n[2]], 1);
+        assert_eq!(ranks[n[3]], 2);
+        assert_eq!(ranks[n[4]], 3);
+
+        // Every edge must stay feasible (length >= minimum_length).
+        for e in graph.edge_indices() {
+            let (t, h) = graph.edge_endpoints(e).unwrap();
+            assert!(ranks[h] - ranks[t] >= 1);
+        }
+
+        let total_length: isize = graph
+            .edge_indices()
+            .map(|e| {
+                let (t, h) = graph.edge_endpoints(e).unwrap();
+                (ranks[h] - ranks[t]) * graph[e] as isize
+            })
+            .sum();
+        assert_eq!(total_length, 15);
+    }
+
+    #[test]
+    fn test_edge_minimum_length_override() {
+        let mut graph: StableDiGraph<Option<()>, usize> = StableDiGraph::new();
+        let n: Vec<_> = (0..2).map(|_| graph.add_node(None)).collect();
+        graph.add_edge(n[0], n[1], 1);
+
+        let ranks_raw = HashMap::from([(n[0], 0), (n[1], 1)]);
+        let default_ranks = Ranks::new(ranks_raw.clone(), &graph, 1);
+        assert_eq!(default_ranks.slack(n[0], n[1]), 0);
+
+        let overridden = Ranks::new(ranks_raw, &graph, 1)
+            .with_edge_minimum_lengths(HashMap::from([((n[0], n[1]), 3)]));
+        // rank1 - rank0 - delta = 1 - 0 - 3 = -2
+        assert_eq!(overridden.slack(n[0], n[1]), -2);
+    }
+
+    #[test]
+    fn test_edge_weight_override() {
+        let mut graph: StableDiGraph<Option<()>, usize> = StableDiGraph::new();
+        let n: Vec<_> = (0..2).map(|_| graph.add_node(None)).collect();
+        graph.add_edge(n[0], n[1], 5);
+
+        let ranks_raw = HashMap::from([(n[0], 0), (n[1], 1)]);
+        let default_ranks = Ranks::new(ranks_raw.clone(), &graph, 1);
+        assert_eq!(default_ranks.edge_weight(n[0], n[1], 5), 5);
+
+        let overridden = Ranks::new(ranks_raw, &graph, 1)
+            .with_edge_weights(HashMap::from([((n[0], n[1]), 20)]));
+        assert_eq!(overridden.edge_weight(n[0], n[1], 5), 20);
+    }
 
     pub(crate) fn create_test_ranking_not_tight() -> Ranks {
         let ranks_raw = HashMap::from([
@@ -105,22 +423,22 @@ pub mod tests {
             (7.into(), 4),
             (8.into(), 6),
         ]);
-        Ranks{ _inner: ranks_raw, minimum_length: 1 }
+        Ranks::new_unchecked(ranks_raw, 1)
     }
 
     #[test]
     fn test_initial_ranking() {
         let graph = create_test_graph::<isize>();
-        let ul_graph = UnlayeredGraph { graph };
+        let ul_graph = UnlayeredGraph::new(graph);
         let ranks = ul_graph.initial_ranking(1).ranks;
-        assert_eq!(ranks._inner.get(&0.into()), Some(&0));
-        assert_eq!(ranks._inner.get(&1.into()), Some(&1));
-        assert_eq!(ranks._inner.get(&2.into()), Some(&2));
-        assert_eq!(ranks._inner.get(&3.into()), Some(&3));
-        assert_eq!(ranks._inner.get(&4.into()), Some(&2));
-        assert_eq!(ranks._inner.get(&5.into()), Some(&2));
-        assert_eq!(ranks._inner.get(&6.into()), Some(&3));
-        assert_eq!(ranks._inner.get(&7.into()), Some(&4));
+        assert_eq!(ranks[0.into()], 0);
+        assert_eq!(ranks[1.into()], 1);
+        assert_eq!(ranks[2.into()], 2);
+        assert_eq!(ranks[3.into()], 3);
+        assert_eq!(ranks[4.into()], 2);
+        assert_eq!(ranks[5.into()], 2);
+        assert_eq!(ranks[6.into()], 3);
+        assert_eq!(ranks[7.into()], 4);
 
         dbg!(&ranks);
     }
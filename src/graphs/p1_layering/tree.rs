@@ -0,0 +1,100 @@
+use std::collections::{HashSet, VecDeque};
+
+use petgraph::stable_graph::NodeIndex;
+
+/// A tight spanning tree built incrementally by depth-first search: starting
+/// from an arbitrary vertex, repeatedly grow the tree with the tight edge
+/// (`slack == 0`) reachable from it, and once no more tight edges are
+/// reachable, pull in the non-tree edge of minimum slack so the tree can
+/// keep growing.
+///
+/// Besides serving the initial feasible ranking, the finished tree (spanning
+/// all vertices) is reused by `Ranks::network_simplex` to compute cut
+/// values.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TightTreeDFSs {
+    vertices: HashSet<NodeIndex>,
+    // tree edges, stored tail -> head as they appear in the original graph
+    edges: HashSet<(NodeIndex, NodeIndex)>,
+}
+
+impl TightTreeDFSs {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn vertices(&self) -> impl Iterator<Item = &NodeIndex> {
+        self.vertices.iter()
+    }
+
+    pub(super) fn edges(&self) -> impl Iterator<Item = &(NodeIndex, NodeIndex)> {
+        self.edges.iter()
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub(super) fn contains_vertex(&self, vertex: NodeIndex) -> bool {
+        self.vertices.contains(&vertex)
+    }
+
+    pub(super) fn insert_vertex(&mut self, vertex: NodeIndex) {
+        self.vertices.insert(vertex);
+    }
+
+    pub(super) fn insert_edge(&mut self, tail: NodeIndex, head: NodeIndex) {
+        self.vertices.insert(tail);
+        self.vertices.insert(head);
+        self.edges.insert((tail, head));
+    }
+
+    pub(super) fn remove_edge(&mut self, tail: NodeIndex, head: NodeIndex) {
+        self.edges.remove(&(tail, head));
+    }
+
+    /// Splits the tree into the two components that result from removing
+    /// `(tail, head)`, returning `(tail_component, head_component)`.
+    ///
+    /// Panics if `(tail, head)` is not a tree edge: the caller is expected to
+    /// only probe edges currently in the tree.
+    pub(super) fn split_on_edge(
+        &self,
+        tail: NodeIndex,
+        head: NodeIndex,
+    ) -> (HashSet<NodeIndex>, HashSet<NodeIndex>) {
+        assert!(self.edges.contains(&(tail, head)));
+
+        let mut tail_component = HashSet::new();
+        let mut queue = VecDeque::from([tail]);
+        tail_component.insert(tail);
+        while let Some(cur) = queue.pop_front() {
+            for (a, b) in &self.edges {
+                if (*a, *b) == (tail, head) {
+                    continue;
+                }
+                let other = if *a == cur {
+                    Some(*b)
+                } else if *b == cur {
+                    Some(*a)
+                } else {
+                    None
+                };
+                if let Some(other) = other {
+                    if tail_component.insert(other) {
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+
+        let head_component = self
+            .vertices
+            .iter()
+            .copied()
+            .filter(|v| !tail_component.contains(v))
+            .collect();
+
+        (tail_component, head_component)
+    }
+}